@@ -2,16 +2,52 @@ use clippy_utils::{
     diagnostics::{multispan_sugg_with_applicability, span_lint_and_then},
     match_def_path, paths,
     source::snippet,
+    ty::is_type_diagnostic_item,
     SpanlessEq,
 };
+use rustc_ast::LitKind;
 use rustc_errors::Applicability;
-use rustc_hir::{Expr, ExprKind, Pat, Stmt, StmtKind, UnOp};
+use rustc_hir::intravisit::{walk_expr, Visitor};
+use rustc_hir::{BinOpKind, Expr, ExprKind, Mutability, Pat, QPath, Stmt, StmtKind, UnOp};
 use rustc_lint::LateContext;
-use rustc_span::Span;
+use rustc_span::{sym, Span, Symbol};
 use std::borrow::Cow;
 
 use super::WHILE_POP_UNWRAP;
 
+/// A collection whose "pop in a loop" anti-pattern we recognize.
+///
+/// The collection is identified by the diagnostic item of its type, which we check against the
+/// receiver's type so a user type reusing the `pop`/`is_empty` method names can't trigger the
+/// lint. `pop` is the name of the popping method used inside the loop; the loop condition
+/// (`!c.is_empty()` or a `c.len()` comparison against `0`) is recognized by `classify_condition`,
+/// so it needs no per-entry field.
+struct PopCollection {
+    /// Diagnostic item of the collection type, checked against the receiver's type.
+    diag_item: Symbol,
+    /// Name of the popping method, matched against the value that gets unwrapped.
+    pop: &'static str,
+}
+
+const POP_COLLECTIONS: &[PopCollection] = &[
+    PopCollection {
+        diag_item: sym::Vec,
+        pop: "pop",
+    },
+    PopCollection {
+        diag_item: sym::VecDeque,
+        pop: "pop_front",
+    },
+    PopCollection {
+        diag_item: sym::VecDeque,
+        pop: "pop_back",
+    },
+    PopCollection {
+        diag_item: sym::BinaryHeap,
+        pop: "pop",
+    },
+];
+
 /// The kind of statement that the `pop()` call appeared in.
 ///
 /// Depending on whether the value was assigned to a variable or not changes what pattern
@@ -22,29 +58,52 @@ enum PopStmt<'hir> {
     /// The pattern of this local variable will be used and the local statement
     /// is deleted in the suggestion.
     Local(&'hir Pat<'hir>),
+    /// `x.pop().unwrap()` was assigned to an already-existing place (`x = v.pop().unwrap();`).
+    /// The left-hand side is used as the bound pattern and the assignment statement is deleted.
+    Assign(&'hir Expr<'hir>),
     /// `x.pop().unwrap()` appeared in an arbitrary expression and was not assigned to a variable.
     /// The suggestion will use some placeholder identifier and the `x.pop().unwrap()` expression
     /// is replaced with that identifier.
     Anonymous,
 }
 
-fn report_lint(cx: &LateContext<'_>, pop_span: Span, pop_stmt_kind: PopStmt<'_>, loop_span: Span, receiver_span: Span) {
+fn report_lint(
+    cx: &LateContext<'_>,
+    pop_span: Span,
+    pop_stmt_kind: PopStmt<'_>,
+    loop_span: Span,
+    receiver_span: Span,
+    pop_method: &str,
+) {
     span_lint_and_then(
         cx,
         WHILE_POP_UNWRAP,
         pop_span,
-        "you seem to be trying to pop elements from a `Vec` in a loop",
+        "you seem to be trying to pop elements from a collection in a loop",
         |diag| {
             let (pat, pop_replacement) = match pop_stmt_kind {
                 PopStmt::Local(pat) => (snippet(cx, pat.span, ".."), String::new()),
+                PopStmt::Assign(lhs) => (snippet(cx, lhs.span, ".."), String::new()),
                 PopStmt::Anonymous => (Cow::Borrowed("element"), "element".into()),
             };
 
-            let loop_replacement = format!("while let Some({}) = {}.pop()", pat, snippet(cx, receiver_span, ".."));
+            // Reusing an existing place (`x = v.pop().unwrap()`) is only `MaybeIncorrect`: the
+            // `while let` introduces a fresh binding scoped to the loop instead of updating the
+            // outer place, so code reading it afterwards would observe a different value.
+            let applicability = match pop_stmt_kind {
+                PopStmt::Assign(_) => Applicability::MaybeIncorrect,
+                PopStmt::Local(_) | PopStmt::Anonymous => Applicability::MachineApplicable,
+            };
+
+            let loop_replacement = format!(
+                "while let Some({}) = {}.{pop_method}()",
+                pat,
+                snippet(cx, receiver_span, "..")
+            );
             multispan_sugg_with_applicability(
                 diag,
                 "consider using a `while..let` loop",
-                Applicability::MachineApplicable,
+                applicability,
                 [(loop_span, loop_replacement), (pop_span, pop_replacement)],
             );
         },
@@ -61,50 +120,267 @@ fn match_method_call(cx: &LateContext<'_>, expr: &Expr<'_>, method: &[&str]) ->
     }
 }
 
-fn is_vec_pop_unwrap(cx: &LateContext<'_>, expr: &Expr<'_>, is_empty_recv: &Expr<'_>) -> bool {
-    if (match_method_call(cx, expr, &paths::OPTION_UNWRAP) || match_method_call(cx, expr, &paths::OPTION_EXPECT))
-        && let ExprKind::MethodCall(_, unwrap_recv, ..) = expr.kind
-        && match_method_call(cx, unwrap_recv, &paths::VEC_POP)
-        && let ExprKind::MethodCall(_, pop_recv, ..) = unwrap_recv.kind
-    {
-        // make sure they're the same `Vec`
-        SpanlessEq::new(cx).eq_expr(pop_recv, is_empty_recv)
-    } else {
-        false
+/// If `expr` is a `c.pop().unwrap()` (or `.expect(..)`) on the same collection as `is_empty_recv`,
+/// returns the name of the popping method (e.g. `"pop"`, `"pop_front"`) to use in the suggestion.
+///
+/// Only the `candidates` allowed by the loop condition are considered, and the
+/// receiver's type is verified against the collection's diagnostic item so that user types
+/// reusing these method names don't trigger the lint.
+fn is_vec_pop_unwrap<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &Expr<'_>,
+    is_empty_recv: &Expr<'_>,
+    candidates: &[&PopCollection],
+) -> Option<&'static str> {
+    if match_method_call(cx, expr, &paths::OPTION_UNWRAP) || match_method_call(cx, expr, &paths::OPTION_EXPECT) {
+        if let ExprKind::MethodCall(_, unwrap_recv, ..) = expr.kind
+            && let ExprKind::MethodCall(pop_seg, pop_recv, ..) = unwrap_recv.kind
+        {
+            let pop_ty = cx.typeck_results().expr_ty(pop_recv).peel_refs();
+            for coll in candidates {
+                if pop_seg.ident.name.as_str() == coll.pop
+                    // the receiver's type must actually be the collection whose method we matched
+                    && is_type_diagnostic_item(cx, pop_ty, coll.diag_item)
+                    // make sure they're the same collection
+                    && SpanlessEq::new(cx).eq_expr(pop_recv, is_empty_recv)
+                {
+                    return Some(coll.pop);
+                }
+            }
+        }
     }
+    None
 }
 
-fn check_local(cx: &LateContext<'_>, stmt: &Stmt<'_>, is_empty_recv: &Expr<'_>, loop_span: Span) {
+fn check_local<'tcx>(
+    cx: &LateContext<'tcx>,
+    stmt: &Stmt<'_>,
+    is_empty_recv: &Expr<'_>,
+    candidates: &[&PopCollection],
+    loop_span: Span,
+) -> bool {
     if let StmtKind::Local(local) = stmt.kind
         && let Some(init) = local.init
-        && is_vec_pop_unwrap(cx, init, is_empty_recv)
+        && let Some(pop_method) = is_vec_pop_unwrap(cx, init, is_empty_recv, candidates)
+    {
+        report_lint(
+            cx,
+            stmt.span,
+            PopStmt::Local(local.pat),
+            loop_span,
+            is_empty_recv.span,
+            pop_method,
+        );
+        return true;
+    }
+    false
+}
+
+/// Returns `true` if `expr`, used as the left-hand side of an assignment, also reads as a valid
+/// irrefutable binding pattern: a bare single-segment identifier. Index,
+/// field and deref places (`arr[i]`, `obj.field`, `*p`) are rejected — reusing their snippet as a
+/// pattern would not compile. Multi-segment paths (`foo::BAR`) are rejected too: they read as a
+/// refutable path pattern, not a fresh binding.
+///
+/// Tuple destructuring (`(a, b) = ..`) and `_ = ..` don't reach here: they desugar to an
+/// `ExprKind::Block`, not an `ExprKind::Assign`, so `check_assign` never matches them.
+fn is_binding_place(expr: &Expr<'_>) -> bool {
+    matches!(
+        expr.kind,
+        ExprKind::Path(QPath::Resolved(None, path)) if path.segments.len() == 1
+    )
+}
+
+fn check_assign<'tcx>(
+    cx: &LateContext<'tcx>,
+    stmt: &Stmt<'_>,
+    is_empty_recv: &Expr<'_>,
+    candidates: &[&PopCollection],
+    loop_span: Span,
+) -> bool {
+    if let StmtKind::Semi(expr) | StmtKind::Expr(expr) = stmt.kind
+        && let ExprKind::Assign(lhs, rhs, _) = expr.kind
+        && is_binding_place(lhs)
+        && let Some(pop_method) = is_vec_pop_unwrap(cx, rhs, is_empty_recv, candidates)
     {
-        report_lint(cx, stmt.span, PopStmt::Local(local.pat), loop_span, is_empty_recv.span);
+        report_lint(cx, stmt.span, PopStmt::Assign(lhs), loop_span, is_empty_recv.span, pop_method);
+        return true;
+    }
+    false
+}
+
+fn check_call_arguments<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &Expr<'_>,
+    is_empty_recv: &Expr<'_>,
+    candidates: &[&PopCollection],
+    loop_span: Span,
+) -> bool {
+    if let ExprKind::MethodCall(.., args, _) | ExprKind::Call(_, args) = expr.kind {
+        let offending_arg = args
+            .iter()
+            .find_map(|arg| is_vec_pop_unwrap(cx, arg, is_empty_recv, candidates).map(|method| (arg.span, method)));
+
+        if let Some((span, pop_method)) = offending_arg {
+            report_lint(cx, span, PopStmt::Anonymous, loop_span, is_empty_recv.span, pop_method);
+            return true;
+        }
     }
+    false
 }
 
-fn check_call_arguments(cx: &LateContext<'_>, stmt: &Stmt<'_>, is_empty_recv: &Expr<'_>, loop_span: Span) {
+/// Checks whether `stmt` is the `pop().unwrap()` we're after, emitting the suggestion and
+/// returning `true` if so.
+fn check_stmt<'tcx>(
+    cx: &LateContext<'tcx>,
+    stmt: &Stmt<'_>,
+    is_empty_recv: &Expr<'_>,
+    candidates: &[&PopCollection],
+    loop_span: Span,
+) -> bool {
+    if check_local(cx, stmt, is_empty_recv, candidates, loop_span)
+        || check_assign(cx, stmt, is_empty_recv, candidates, loop_span)
+    {
+        return true;
+    }
     if let StmtKind::Semi(expr) | StmtKind::Expr(expr) = stmt.kind {
-        if let ExprKind::MethodCall(.., args, _) | ExprKind::Call(_, args) = expr.kind {
-            let offending_arg = args
-                .iter()
-                .find_map(|arg| is_vec_pop_unwrap(cx, arg, is_empty_recv).then_some(arg.span));
+        return check_call_arguments(cx, expr, is_empty_recv, candidates, loop_span);
+    }
+    false
+}
 
-            if let Some(offending_arg) = offending_arg {
-                report_lint(cx, offending_arg, PopStmt::Anonymous, loop_span, is_empty_recv.span);
-            }
+/// Detects whether a preceding statement makes moving the `pop()` into the loop header unsound.
+///
+/// This is true if the guarded collection is observably mutated (or has its length changed) — any
+/// method call, index, `&mut` borrow, or whole reassignment whose receiver is the same collection
+/// is treated as a mutation, since it could change the element the eventual `pop()` would return.
+/// It is also true if the statement can divert control flow (`continue`/`break`/`return`/`?`),
+/// which would make the pop conditionally reached rather than run every iteration.
+struct MutationVisitor<'a, 'b, 'tcx> {
+    cx: &'a LateContext<'tcx>,
+    target: &'b Expr<'tcx>,
+    mutated: bool,
+}
+
+impl<'a, 'b, 'tcx> Visitor<'tcx> for MutationVisitor<'a, 'b, 'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        if self.mutated {
+            return;
+        }
+
+        // Diverging control flow would make the later `pop()` conditional on reaching it.
+        if matches!(expr.kind, ExprKind::Ret(_) | ExprKind::Break(..) | ExprKind::Continue(_)) {
+            self.mutated = true;
+            return;
+        }
+
+        let receiver = match expr.kind {
+            ExprKind::MethodCall(_, recv, ..) => Some(recv),
+            ExprKind::Index(base, ..) => Some(base),
+            ExprKind::AddrOf(_, Mutability::Mut, inner) => Some(inner),
+            // `v = ..` / `v += ..` entirely replaces the collection we're about to pop from.
+            ExprKind::Assign(lhs, ..) | ExprKind::AssignOp(_, lhs, _) => Some(lhs),
+            _ => None,
+        };
+        if let Some(receiver) = receiver
+            && SpanlessEq::new(self.cx).eq_expr(receiver, self.target)
+        {
+            self.mutated = true;
+            return;
         }
+
+        walk_expr(self, expr);
     }
 }
 
-pub(super) fn check<'tcx>(cx: &LateContext<'tcx>, full_cond: &'tcx Expr<'_>, body: &'tcx Expr<'_>, loop_span: Span) {
+/// Returns `true` if evaluating `stmt` could mutate `target` (or skip the pop) before the `pop()`
+/// is reached.
+fn mutates_target<'tcx>(cx: &LateContext<'tcx>, stmt: &'tcx Stmt<'tcx>, target: &Expr<'_>) -> bool {
+    let mut visitor = MutationVisitor {
+        cx,
+        target,
+        mutated: false,
+    };
+    visitor.visit_stmt(stmt);
+    visitor.mutated
+}
+
+/// Returns the receiver of a `.len()` method call, if `expr` is one.
+fn len_recv<'a>(expr: &'a Expr<'a>) -> Option<&'a Expr<'a>> {
+    if let ExprKind::MethodCall(seg, recv, [], _) = expr.kind
+        && seg.ident.name == sym::len
+    {
+        Some(recv)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if `expr` is the integer literal `0`.
+fn is_zero(expr: &Expr<'_>) -> bool {
+    matches!(expr.kind, ExprKind::Lit(lit) if matches!(lit.node, LitKind::Int(0, _)))
+}
+
+/// Extracts the guarded collection from a loop condition that tests "the collection is not empty".
+///
+/// Recognized forms, with the candidate collections each one allows:
+/// * `!c.is_empty()` — only collections whose type matches `c`'s.
+/// * `c.len() > 0`, `c.len() != 0`, `0 < c.len()`, `0 != c.len()` — any known collection (the
+///   receiver's type is verified later when matching the `pop` call).
+///
+/// `>=`, `==` and `<` comparisons are deliberately rejected: they are either always true or never
+/// let the loop body run, so rewriting them would be wrong.
+fn classify_condition<'tcx>(
+    cx: &LateContext<'tcx>,
+    full_cond: &'tcx Expr<'tcx>,
+) -> Option<(&'tcx Expr<'tcx>, Vec<&'static PopCollection>)> {
     if let ExprKind::Unary(UnOp::Not, cond) = full_cond.kind
-        && let ExprKind::MethodCall(_, is_empty_recv, _, _) = cond.kind
-        && match_method_call(cx, cond, &paths::VEC_IS_EMPTY)
-        && let ExprKind::Block(body, _) = body.kind
-        && let Some(stmt) = body.stmts.first()
+        && let ExprKind::MethodCall(seg, is_empty_recv, [], _) = cond.kind
+        && seg.ident.name.as_str() == "is_empty"
+    {
+        let recv_ty = cx.typeck_results().expr_ty(is_empty_recv).peel_refs();
+        let candidates = POP_COLLECTIONS
+            .iter()
+            .filter(|coll| is_type_diagnostic_item(cx, recv_ty, coll.diag_item))
+            .collect::<Vec<_>>();
+        return (!candidates.is_empty()).then_some((is_empty_recv, candidates));
+    }
+
+    if let ExprKind::Binary(op, lhs, rhs) = full_cond.kind {
+        let recv = match op.node {
+            // `c.len() > 0` / `c.len() != 0`
+            BinOpKind::Gt | BinOpKind::Ne if is_zero(rhs) => len_recv(lhs),
+            // `0 < c.len()` / `0 != c.len()`
+            BinOpKind::Lt | BinOpKind::Ne if is_zero(lhs) => len_recv(rhs),
+            _ => None,
+        };
+        if let Some(recv) = recv {
+            return Some((recv, POP_COLLECTIONS.iter().collect()));
+        }
+    }
+
+    None
+}
+
+pub(super) fn check<'tcx>(cx: &LateContext<'tcx>, full_cond: &'tcx Expr<'_>, body: &'tcx Expr<'_>, loop_span: Span) {
+    if let Some((is_empty_recv, candidates)) = classify_condition(cx, full_cond)
+        && let ExprKind::Block(block, _) = body.kind
     {
-        check_local(cx, stmt, is_empty_recv, loop_span);
-        check_call_arguments(cx, stmt, is_empty_recv, loop_span);
+        // Walk the statements in order looking for the first top-level `pop().unwrap()` on the
+        // guarded collection. We fire on the first match, but bail as soon as a preceding statement
+        // could have mutated the collection (or its length), since rewriting would then change
+        // behavior. Only top-level statements are inspected, so a pop nested inside an `if`/`match`
+        // or inner loop is never reached unconditionally and won't trigger the lint.
+        for stmt in block.stmts {
+            if check_stmt(cx, stmt, is_empty_recv, &candidates, loop_span) {
+                return;
+            }
+            if mutates_target(cx, stmt, is_empty_recv) {
+                return;
+            }
+        }
+        if let Some(expr) = block.expr {
+            check_call_arguments(cx, expr, is_empty_recv, &candidates, loop_span);
+        }
     }
 }
@@ -0,0 +1,147 @@
+//@run-rustfix
+
+#![warn(clippy::while_pop_unwrap)]
+#![allow(clippy::unnecessary_operation, clippy::no_effect, unused_variables, unused_mut)]
+#![allow(unused_assignments, dead_code, unused_comparisons)]
+#![allow(clippy::len_zero, clippy::never_loop, clippy::absurd_extreme_comparisons)]
+
+use std::collections::{BinaryHeap, VecDeque};
+
+fn accept_i32(_: i32) {}
+
+fn main() {
+    let mut numbers = vec![1, 2, 3, 4, 5];
+    while !numbers.is_empty() {
+        let number = numbers.pop().unwrap();
+    }
+
+    // `VecDeque::pop_front`
+    let mut front: VecDeque<i32> = VecDeque::from([1, 2, 3]);
+    while !front.is_empty() {
+        let x = front.pop_front().unwrap();
+    }
+
+    // `VecDeque::pop_back`
+    let mut back: VecDeque<i32> = VecDeque::from([1, 2, 3]);
+    while !back.is_empty() {
+        let x = back.pop_back().unwrap();
+    }
+
+    // `BinaryHeap::pop`
+    let mut heap: BinaryHeap<i32> = BinaryHeap::from([1, 2, 3]);
+    while !heap.is_empty() {
+        let x = heap.pop().unwrap();
+    }
+
+    // length-based condition: `len() > 0`
+    let mut len_gt = vec![1, 2, 3];
+    while len_gt.len() > 0 {
+        let x = len_gt.pop().unwrap();
+    }
+
+    // length-based condition: `len() != 0`
+    let mut len_ne = vec![1, 2, 3];
+    while len_ne.len() != 0 {
+        let x = len_ne.pop().unwrap();
+    }
+
+    // length-based condition: `0 < len()`
+    let mut zero_lt = vec![1, 2, 3];
+    while 0 < zero_lt.len() {
+        let x = zero_lt.pop().unwrap();
+    }
+
+    // length-based condition: `0 != len()`
+    let mut zero_ne = vec![1, 2, 3];
+    while 0 != zero_ne.len() {
+        let x = zero_ne.pop().unwrap();
+    }
+
+    // as a call argument
+    let mut args = vec![1, 2, 3];
+    while !args.is_empty() {
+        accept_i32(args.pop().unwrap());
+    }
+
+    // reassignment into an existing binding (downgraded to `MaybeIncorrect`, so rustfix leaves it)
+    let mut reassign = vec![1, 2, 3];
+    let mut x = 0;
+    while !reassign.is_empty() {
+        x = reassign.pop().unwrap();
+    }
+}
+
+fn assign_should_not_lint() {
+    // Assigning into a non-binding place (index/field/deref) must not fire: the place cannot be
+    // reused as a binding pattern, so the suggestion would not compile.
+    let mut indexed = vec![1, 2, 3];
+    let mut slot = [0; 1];
+    while !indexed.is_empty() {
+        slot[0] = indexed.pop().unwrap();
+    }
+
+    struct Holder {
+        value: i32,
+    }
+    let mut fielded = vec![1, 2, 3];
+    let mut holder = Holder { value: 0 };
+    while !fielded.is_empty() {
+        holder.value = fielded.pop().unwrap();
+    }
+
+    let mut deref = vec![1, 2, 3];
+    let mut boxed = Box::new(0);
+    while !deref.is_empty() {
+        *boxed = deref.pop().unwrap();
+    }
+
+    // Tuple-destructuring assignment desugars to an `ExprKind::Block`, never an `ExprKind::Assign`,
+    // so `check_assign` never sees it. Documented limitation, pinned here rather than left untested.
+    let mut pairs: Vec<(i32, i32)> = vec![(1, 2), (3, 4)];
+    let (mut pa, mut pb) = (0, 0);
+    while !pairs.is_empty() {
+        (pa, pb) = pairs.pop().unwrap();
+    }
+}
+
+fn should_not_lint() {
+    // A user type that happens to expose `pop`/`pop_front`/`is_empty` must not fire: the type is
+    // not one of the known standard collections.
+    struct FakeVec;
+    impl FakeVec {
+        fn is_empty(&self) -> bool {
+            true
+        }
+        fn pop(&mut self) -> Option<i32> {
+            None
+        }
+        fn pop_front(&mut self) -> Option<i32> {
+            None
+        }
+    }
+
+    let mut fake = FakeVec;
+    while !fake.is_empty() {
+        let x = fake.pop().unwrap();
+    }
+    while !fake.is_empty() {
+        let x = fake.pop_front().unwrap();
+    }
+
+    // Length comparisons that are always-true or never-entering must be rejected.
+    let mut v = vec![1, 2, 3];
+    while v.len() >= 0 {
+        let x = v.pop().unwrap();
+        break;
+    }
+    while v.len() == 0 {
+        let x = v.pop().unwrap();
+    }
+
+    // The collection is mutated before the pop, so the rewrite would change behavior.
+    let mut mutated = vec![1, 2, 3];
+    while !mutated.is_empty() {
+        mutated.push(0);
+        let x = mutated.pop().unwrap();
+    }
+}